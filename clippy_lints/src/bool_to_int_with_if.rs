@@ -0,0 +1,152 @@
+// Copyright 2014-2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Checks for `if`/`else` expressions whose two branches are just the
+//! integer literals `1` and `0`.
+//!
+//! For example, the lint would catch:
+//!
+//! ```rust,ignore
+//! if x > 0 {
+//!     1
+//! } else {
+//!     0
+//! }
+//! ```
+//!
+//! This lint is **warn** by default
+
+use crate::rustc::lint::{EarlyContext, EarlyLintPass, LintArray, LintPass};
+use crate::rustc::{declare_tool_lint, lint_array};
+use if_chain::if_chain;
+use crate::syntax::ast;
+
+use crate::utils::{in_macro, span_lint_and_then};
+use crate::utils::sugg::Sugg;
+use crate::rustc_errors::Applicability;
+
+/// **What it does:** Checks for `if`/`else` expressions that reduce to the
+/// integer literals `1` and `0` depending on the condition.
+///
+/// **Why is this bad?** This is a roundabout way to convert a `bool` to an
+/// integer; casting the condition directly says the same thing more
+/// directly.
+///
+/// **Known problems:** This is an `EarlyLintPass`, so the integer type
+/// expected at the use site (`i32`, `usize`, …) isn't known; the suggestion
+/// casts to `_` and lets inference pick it, which works as long as the
+/// surrounding context constrains the type (it almost always does, since
+/// that's exactly what the replaced code already did).
+///
+/// **Example:**
+/// ```rust,ignore
+/// if x > 0 {
+///     1
+/// } else {
+///     0
+/// }
+/// ```
+///
+/// Should be written:
+///
+/// ```rust,ignore
+/// (x > 0) as _
+/// ```
+declare_clippy_lint! {
+    pub BOOL_TO_INT_WITH_IF,
+    style,
+    "using an `if`/`else` to convert a boolean to 0 or 1"
+}
+
+#[derive(Copy, Clone)]
+pub struct BoolToIntWithIf;
+
+impl LintPass for BoolToIntWithIf {
+    fn get_lints(&self) -> LintArray {
+        lint_array!(BOOL_TO_INT_WITH_IF)
+    }
+}
+
+impl EarlyLintPass for BoolToIntWithIf {
+    fn check_expr(&mut self, cx: &EarlyContext<'_>, expr: &ast::Expr) {
+        if !in_macro(expr.span) {
+            check_if(cx, expr)
+        }
+    }
+}
+
+fn check_if(cx: &EarlyContext<'_>, expr: &ast::Expr) {
+    if_chain! {
+        if let ast::ExprKind::If(ref check, ref then, Some(ref else_)) = expr.node;
+        if let Some(then_expr) = expr_block(then);
+        if let ast::ExprKind::Block(ref else_block, _) = else_.node;
+        if let Some(else_expr) = expr_block(else_block);
+        if !in_macro(then_expr.span) && !in_macro(else_expr.span);
+        if let Some(then_val) = int_lit_value(then_expr);
+        if let Some(else_val) = int_lit_value(else_expr);
+        if let Some(invert) = zero_one_pair(then_val, else_val);
+        then {
+            let sugg = Sugg::ast(cx, check, "..");
+            let sugg = if invert { sugg.not() } else { sugg };
+            span_lint_and_then(
+                cx,
+                BOOL_TO_INT_WITH_IF,
+                expr.span,
+                "boolean to int conversion using if",
+                |db| {
+                    db.span_suggestion_with_applicability(
+                        expr.span,
+                        "replace with",
+                        format!("({}) as _", sugg),
+                        // the integer type expected at the use site (`i32`, `usize`, …) isn't
+                        // known at this point, so this can never be more than `MaybeIncorrect`
+                        Applicability::MaybeIncorrect,
+                    );
+                },
+            );
+        }
+    }
+}
+
+/// If `then_val`/`else_val` are `1`/`0` in either order, returns whether the condition needs to
+/// be inverted (i.e. the `then` branch was `0`).
+fn zero_one_pair(then_val: u128, else_val: u128) -> Option<bool> {
+    match (then_val, else_val) {
+        (1, 0) => Some(false),
+        (0, 1) => Some(true),
+        _ => None,
+    }
+}
+
+/// If the block consists of only a tail expression (no trailing `;`), return it.
+fn expr_block(block: &ast::Block) -> Option<&ast::Expr> {
+    let mut it = block.stmts.iter();
+
+    if let (Some(stmt), None) = (it.next(), it.next()) {
+        match stmt.node {
+            ast::StmtKind::Expr(ref expr) => Some(expr),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+/// If `expr` is an (optionally parenthesized) integer literal, return its value.
+fn int_lit_value(expr: &ast::Expr) -> Option<u128> {
+    match expr.node {
+        ast::ExprKind::Lit(ref lit) => match lit.node {
+            ast::LitKind::Int(value, _) => Some(value),
+            _ => None,
+        },
+        ast::ExprKind::Paren(ref inner) => int_lit_value(inner),
+        _ => None,
+    }
+}
@@ -27,6 +27,7 @@ use crate::rustc::{declare_tool_lint, lint_array};
 use if_chain::if_chain;
 use crate::syntax::ast;
 
+use crate::syntax::source_map::Span;
 use crate::utils::{in_macro, snippet_block, span_lint_and_sugg, span_lint_and_then};
 use crate::utils::sugg::Sugg;
 use crate::rustc_errors::Applicability;
@@ -81,12 +82,53 @@ declare_clippy_lint! {
     "`if`s that can be collapsed (e.g. `if x { if y { ... } }` and `else { if x { ... } }`)"
 }
 
+/// **What it does:** Checks for `if`/`else` expressions whose branches start
+/// or end with the same sequence of statements.
+///
+/// **Why is this bad?** Code duplicated between both branches of a
+/// conditional is easy to miss and hurts maintainability; if it doesn't
+/// actually depend on the condition it should be hoisted out of the `if`.
+///
+/// **Known problems:** The suggestion only looks at whole statements, so it
+/// won't spot duplication that starts or ends partway through one. The check
+/// for `return`/`break`/`continue`/`?` only looks at the top of each shared
+/// statement and inside nested blocks, `if`s and `match`es; one found
+/// anywhere else (e.g. in a `let` initializer, a call's arguments, a loop or
+/// a closure) doesn't stop the lint.
+///
+/// **Example:**
+/// ```rust,ignore
+/// if foo {
+///     println!("bar");
+///     println!("baz");
+/// } else {
+///     println!("bar");
+///     println!("quz");
+/// }
+/// ```
+///
+/// Should be written:
+///
+/// ```rust,ignore
+/// println!("bar");
+/// if foo {
+///     println!("baz");
+/// } else {
+///     println!("quz");
+/// }
+/// ```
+declare_clippy_lint! {
+    pub BRANCHES_SHARING_CODE,
+    style,
+    "`if`/`else` branches that share a common prefix or suffix of statements"
+}
+
 #[derive(Copy, Clone)]
 pub struct CollapsibleIf;
 
 impl LintPass for CollapsibleIf {
     fn get_lints(&self) -> LintArray {
-        lint_array!(COLLAPSIBLE_IF)
+        lint_array!(COLLAPSIBLE_IF, BRANCHES_SHARING_CODE)
     }
 }
 
@@ -96,6 +138,23 @@ impl EarlyLintPass for CollapsibleIf {
             check_if(cx, expr)
         }
     }
+
+    fn check_stmt(&mut self, cx: &EarlyContext<'_>, stmt: &ast::Stmt) {
+        // BRANCHES_SHARING_CODE hoists the shared statements out in front of the `if`, which only
+        // produces a valid expression when the `if` itself is a statement (its value, if any, is
+        // discarded); in value position (`let x = if c { a(); E } else { a(); F };`) the same
+        // rewrite would leave a `let` with no body and a dangling `if` expression.
+        match stmt.node {
+            ast::StmtKind::Semi(ref expr) | ast::StmtKind::Expr(ref expr) if !in_macro(expr.span) => {
+                if let ast::ExprKind::If(ref check, ref then, Some(ref else_)) = expr.node {
+                    if let ast::ExprKind::Block(ref else_block, _) = else_.node {
+                        check_branches_sharing_code(cx, expr.span, check, then, else_block);
+                    }
+                }
+            },
+            _ => (),
+        }
+    }
 }
 
 fn check_if(cx: &EarlyContext<'_>, expr: &ast::Expr) {
@@ -172,3 +231,121 @@ fn expr_block(block: &ast::Block) -> Option<&ast::Expr> {
         None
     }
 }
+
+fn check_branches_sharing_code(
+    cx: &EarlyContext<'_>,
+    if_span: Span,
+    check: &ast::Expr,
+    then: &ast::Block,
+    else_: &ast::Block,
+) {
+    if in_macro(then.span) || in_macro(else_.span) {
+        return;
+    }
+
+    let shorter_len = then.stmts.len().min(else_.stmts.len());
+
+    let prefix_len = then
+        .stmts
+        .iter()
+        .zip(else_.stmts.iter())
+        .take_while(|(a, b)| eq_stmt(cx, a, b))
+        .count();
+
+    // The suffix can't eat into whatever was already claimed by the prefix, or the two halves
+    // would overlap when one branch is shorter than the other.
+    let suffix_len = then
+        .stmts
+        .iter()
+        .rev()
+        .zip(else_.stmts.iter().rev())
+        .take(shorter_len - prefix_len)
+        .take_while(|(a, b)| eq_stmt(cx, a, b))
+        .count();
+
+    if prefix_len == 0 && suffix_len == 0 {
+        return;
+    }
+
+    let prefix_stmts = &then.stmts[..prefix_len];
+    let suffix_stmts = &then.stmts[then.stmts.len() - suffix_len..];
+    if prefix_stmts.iter().chain(suffix_stmts.iter()).any(has_interrupt) {
+        return;
+    }
+
+    span_lint_and_then(
+        cx,
+        BRANCHES_SHARING_CODE,
+        if_span,
+        "all branches of this `if` and `else` share some statements that could be hoisted out of it",
+        |db| {
+            let middle_then = middle_stmts_snippet(cx, &then.stmts, prefix_len, suffix_len);
+            let middle_else = middle_stmts_snippet(cx, &else_.stmts, prefix_len, suffix_len);
+
+            let mut suggestion = String::new();
+            for stmt in prefix_stmts {
+                suggestion.push_str(&snippet_block(cx, stmt.span, ".."));
+                suggestion.push('\n');
+            }
+            suggestion.push_str(&format!(
+                "if {} {{\n{}\n}} else {{\n{}\n}}",
+                Sugg::ast(cx, check, ".."),
+                middle_then,
+                middle_else,
+            ));
+            for stmt in suffix_stmts {
+                suggestion.push('\n');
+                suggestion.push_str(&snippet_block(cx, stmt.span, ".."));
+            }
+            db.span_suggestion_with_applicability(
+                if_span,
+                "consider hoisting the shared statements out, e.g.",
+                suggestion,
+                Applicability::MaybeIncorrect, // the moved code may reference branch-local bindings
+            );
+        },
+    );
+}
+
+/// Renders the statements that are left in a branch after its shared prefix/suffix have been
+/// hoisted out.
+fn middle_stmts_snippet(cx: &EarlyContext<'_>, stmts: &[ast::Stmt], prefix_len: usize, suffix_len: usize) -> String {
+    stmts[prefix_len..stmts.len() - suffix_len]
+        .iter()
+        .map(|stmt| snippet_block(cx, stmt.span, "..").into_owned())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Compares two statements by their source snippets. This is a textual comparison, not a
+/// structural one: statements that are equivalent but written differently (e.g. with different
+/// spacing or renamed bindings) don't match.
+fn eq_stmt(cx: &EarlyContext<'_>, a: &ast::Stmt, b: &ast::Stmt) -> bool {
+    snippet_block(cx, a.span, "..") == snippet_block(cx, b.span, "..")
+}
+
+/// Checks whether a statement contains control flow that could change meaning if moved out of
+/// the branch it's currently in. Only the statement's own expression and nested blocks, `if`s and
+/// `match`es are searched; `let` statements are never reported.
+fn has_interrupt(stmt: &ast::Stmt) -> bool {
+    match stmt.node {
+        ast::StmtKind::Expr(ref e) | ast::StmtKind::Semi(ref e) => expr_has_interrupt(e),
+        _ => false,
+    }
+}
+
+fn expr_has_interrupt(expr: &ast::Expr) -> bool {
+    match expr.node {
+        ast::ExprKind::Ret(_) | ast::ExprKind::Break(..) | ast::ExprKind::Continue(_) | ast::ExprKind::Try(_) => true,
+        ast::ExprKind::Block(ref block, _) => block.stmts.iter().any(has_interrupt),
+        ast::ExprKind::If(ref check, ref then, ref else_) => {
+            expr_has_interrupt(check)
+                || then.stmts.iter().any(has_interrupt)
+                || else_.as_ref().map_or(false, |e| expr_has_interrupt(e))
+        },
+        ast::ExprKind::Match(ref scrutinee, ref arms) => {
+            expr_has_interrupt(scrutinee) || arms.iter().any(|arm| expr_has_interrupt(&arm.body))
+        },
+        _ => false,
+    }
+}
@@ -0,0 +1,335 @@
+// Copyright 2014-2018 The Rust Project Developers. See the COPYRIGHT
+// file at the top-level directory of this distribution.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+
+//! Checks for `match`/`if let` arms whose body is itself a `match`/`if let`
+//! with a single non-wildcard arm that drills into the binding introduced by
+//! the outer arm.
+//!
+//! For example, the lint would catch:
+//!
+//! ```rust,ignore
+//! match outer {
+//!     Some(x) => match x {
+//!         Foo::Bar(y) => println!("{}", y),
+//!         _ => {},
+//!     },
+//!     _ => {},
+//! }
+//! ```
+//!
+//! This lint is **warn** by default
+
+use crate::rustc::lint::{EarlyContext, EarlyLintPass, LintArray, LintPass};
+use crate::rustc::{declare_tool_lint, lint_array};
+use if_chain::if_chain;
+use crate::syntax::ast;
+use crate::syntax::ptr::P;
+use crate::syntax::source_map::Span;
+use crate::syntax::visit::{self, Visitor};
+
+use crate::utils::{in_macro, snippet, snippet_block, span_lint_and_then};
+use crate::rustc_errors::Applicability;
+
+/// **What it does:** Checks for `match` arms or `if let` bodies that consist
+/// of a single `match`/`if let` scrutinizing the binding the outer pattern
+/// just introduced, where that inner construct has a single non-wildcard arm.
+///
+/// **Why is this bad?** The outer and inner patterns can be merged into a
+/// single pattern on the outer `match`/`if let`, removing a level of
+/// nesting.
+///
+/// **Known problems:** Only considers a single, simple binding introduced by
+/// the outer pattern (e.g. `Some(x)`); outer patterns with multiple
+/// bindings, or-patterns and guards on either level are not handled.
+///
+/// **Example:**
+/// ```rust,ignore
+/// match outer {
+///     Some(x) => match x {
+///         Foo::Bar(y) => println!("{}", y),
+///         _ => {},
+///     },
+///     _ => {},
+/// }
+/// ```
+///
+/// Should be written:
+///
+/// ```rust,ignore
+/// match outer {
+///     Some(Foo::Bar(y)) => println!("{}", y),
+///     _ => {},
+/// }
+/// ```
+declare_clippy_lint! {
+    pub COLLAPSIBLE_MATCH,
+    style,
+    "a nested `match` or `if let` that can be collapsed into the outer pattern"
+}
+
+#[derive(Copy, Clone)]
+pub struct CollapsibleMatch;
+
+impl LintPass for CollapsibleMatch {
+    fn get_lints(&self) -> LintArray {
+        lint_array!(COLLAPSIBLE_MATCH)
+    }
+}
+
+impl EarlyLintPass for CollapsibleMatch {
+    fn check_expr(&mut self, cx: &EarlyContext<'_>, expr: &ast::Expr) {
+        if in_macro(expr.span) {
+            return;
+        }
+        match expr.node {
+            ast::ExprKind::Match(_, ref arms) => {
+                let outer_has_wild = arms.iter().any(|arm| arm.guard.is_none() && is_wild_arm(arm));
+                for arm in arms {
+                    if arm.guard.is_none() && !is_wild_arm(arm) {
+                        check_match_arm(cx, &arm.pats, &arm.body, outer_has_wild);
+                    }
+                }
+            },
+            // an `if let` without an `else` has no fallback of its own for whatever the inner
+            // construct doesn't cover
+            ast::ExprKind::IfLet(ref pats, _, ref body, None) => check_if_let_arm(cx, pats, body, false),
+            ast::ExprKind::IfLet(ref pats, _, ref body, Some(_)) => check_if_let_arm(cx, pats, body, true),
+            _ => (),
+        }
+    }
+}
+
+fn is_wild_arm(arm: &ast::Arm) -> bool {
+    match &*arm.pats {
+        [pat] => is_wild(pat),
+        _ => false,
+    }
+}
+
+/// Checks a single match arm, whose body is an expression that may or may not be wrapped in a
+/// block, for a collapsible inner `match`/`if let`. `outer_has_fallback` says whether the outer
+/// `match`/`if let` has some other arm/branch able to absorb cases the inner construct doesn't
+/// itself cover.
+fn check_match_arm(cx: &EarlyContext<'_>, outer_pats: &[P<ast::Pat>], body: &ast::Expr, outer_has_fallback: bool) {
+    if_chain! {
+        if let [outer_pat] = &**outer_pats;
+        if let Some(binding) = single_bound_ident(outer_pat);
+        if let Some(inner) = as_single_expr(body);
+        if !in_macro(inner.span);
+        if body.span.ctxt() == inner.span.ctxt();
+        then {
+            check_inner_construct(cx, outer_pat, body.span, &binding, inner, outer_has_fallback);
+        }
+    }
+}
+
+/// Checks the body of an `if let`, which is always a brace-delimited block, for a collapsible
+/// inner `match`/`if let`.
+fn check_if_let_arm(cx: &EarlyContext<'_>, outer_pats: &[P<ast::Pat>], body: &ast::Block, outer_has_fallback: bool) {
+    if_chain! {
+        if let [outer_pat] = &**outer_pats;
+        if let Some(binding) = single_bound_ident(outer_pat);
+        if let Some(inner) = expr_block(body);
+        if !in_macro(inner.span);
+        if body.span.ctxt() == inner.span.ctxt();
+        then {
+            check_inner_construct(cx, outer_pat, body.span, &binding, inner, outer_has_fallback);
+        }
+    }
+}
+
+/// If `body` is a block containing a single expression, unwrap it; otherwise `body` itself is
+/// the single expression (as in a brace-less match arm, e.g. `Some(x) => match x { .. }`).
+fn as_single_expr(body: &ast::Expr) -> Option<&ast::Expr> {
+    match body.node {
+        ast::ExprKind::Block(ref block, _) => expr_block(block),
+        _ => Some(body),
+    }
+}
+
+/// Given the outer pattern's single binding and the expression it was found to contain, checks
+/// whether that expression is a collapsible `match`/`if let` and emits a suggestion.
+///
+/// Collapsing narrows the outer arm's pattern down to whatever the inner construct matched, so
+/// whatever the inner construct *didn't* match needs somewhere else to go: either the inner
+/// construct was already exhaustive on its own (nothing is lost), or the leftover cases fall
+/// through to a no-op (the inner wildcard arm's body, or a missing `if let` `else`) and the outer
+/// `match`/`if let` has its own fallback to catch them once collapsed.
+fn check_inner_construct(cx: &EarlyContext<'_>, outer_pat: &ast::Pat, body_span: Span, binding: &ast::Ident, inner: &ast::Expr, outer_has_fallback: bool) {
+    match inner.node {
+        ast::ExprKind::Match(ref scrutinee, ref inner_arms) => {
+            if_chain! {
+                if let Some((inner_arm, wild_arm)) = single_non_wild_arm(inner_arms);
+                if wild_arm.map_or(true, |w| outer_has_fallback && is_noop_body(&w.body));
+                if let [inner_pat] = &*inner_arm.pats;
+                then {
+                    let inner_body = snippet_block(cx, inner_arm.body.span, "..").into_owned();
+                    let reuses_binding = expr_uses_ident(&inner_arm.body, binding.name);
+                    suggest_collapse(cx, outer_pat, body_span, binding, scrutinee, inner_pat, &inner_body, reuses_binding);
+                }
+            }
+        },
+        // an `if let` without an `else` silently does nothing for non-matching values, which is
+        // exactly like a no-op wildcard arm -- it still needs the outer fallback to absorb them
+        ast::ExprKind::IfLet(ref inner_pats, ref scrutinee, ref inner_body, None) if outer_has_fallback => {
+            if let [inner_pat] = &**inner_pats {
+                let body_text = snippet_block(cx, inner_body.span, "..").into_owned();
+                let reuses_binding = block_uses_ident(inner_body, binding.name);
+                suggest_collapse(cx, outer_pat, body_span, binding, scrutinee, inner_pat, &body_text, reuses_binding);
+            }
+        },
+        _ => (),
+    }
+}
+
+/// Checks whether `body` is a no-op (an empty block `{}` or the unit value `()`)
+/// -- the only shape of wildcard arm whose removal doesn't change what running the code does for
+/// the values it used to match.
+fn is_noop_body(body: &ast::Expr) -> bool {
+    match body.node {
+        ast::ExprKind::Block(ref block, _) => block.stmts.is_empty(),
+        ast::ExprKind::Tup(ref items) => items.is_empty(),
+        _ => false,
+    }
+}
+
+fn suggest_collapse(
+    cx: &EarlyContext<'_>,
+    outer_pat: &ast::Pat,
+    body_span: Span,
+    binding: &ast::Ident,
+    scrutinee: &ast::Expr,
+    inner_pat: &ast::Pat,
+    inner_body_snippet: &str,
+    inner_body_reuses_binding: bool,
+) {
+    if_chain! {
+        if let ast::ExprKind::Path(None, ref path) = scrutinee.node;
+        if let [seg] = &*path.segments;
+        if seg.ident.name == binding.name;
+        // if the kept body still refers to the outer binding, collapsing it away would leave a
+        // dangling reference, e.g. `Some(x) => match x { Foo(y) => f(x, y), _ => {} }`
+        if !inner_body_reuses_binding;
+        then {
+            let merged_pat = merge_pattern(cx, outer_pat, binding, &snippet_block(cx, inner_pat.span, "..").into_owned());
+            // the literal text between the outer pattern and its body (`=>` for a match arm,
+            // `= scrutinee` for an `if let`) is kept verbatim so the suggestion fits either form
+            let gap = snippet(cx, outer_pat.span.between(body_span), "..").into_owned();
+            span_lint_and_then(
+                cx,
+                COLLAPSIBLE_MATCH,
+                outer_pat.span.to(body_span),
+                "this `match`/`if let` can be collapsed into the outer pattern",
+                |db| {
+                    db.span_suggestion_with_applicability(
+                        outer_pat.span.to(body_span),
+                        "try",
+                        format!("{}{}{}", merged_pat, gap, inner_body_snippet),
+                        Applicability::MaybeIncorrect, // may need reformatting, and could shadow other bindings
+                    );
+                },
+            );
+        }
+    }
+}
+
+/// Splices `inner_pat`'s snippet in place of the identifier `binding` inside `outer_pat`'s own
+/// snippet, e.g. turns `Some(x)` crossed with `Foo::Bar(y)` into `Some(Foo::Bar(y))`.
+fn merge_pattern(cx: &EarlyContext<'_>, outer_pat: &ast::Pat, binding: &ast::Ident, inner_pat_snippet: &str) -> String {
+    let before = snippet(cx, outer_pat.span.until(binding.span), "");
+    let after = snippet(cx, binding.span.shrink_to_hi().to(outer_pat.span.shrink_to_hi()), "");
+    format!("{}{}{}", before, inner_pat_snippet, after)
+}
+
+/// If the arm list is a single meaningful arm plus at most one wildcard arm, returns the
+/// meaningful arm together with the wildcard arm, if any. The caller still has to decide whether
+/// it's safe to drop that wildcard arm (its body must be a no-op, and the outer construct needs
+/// its own fallback to absorb the cases it used to catch).
+fn single_non_wild_arm(arms: &[ast::Arm]) -> Option<(&ast::Arm, Option<&ast::Arm>)> {
+    let mut meaningful = None;
+    let mut wild = None;
+    for arm in arms {
+        if arm.guard.is_some() {
+            return None;
+        }
+        match &*arm.pats {
+            [pat] if is_wild(pat) && wild.is_none() => wild = Some(arm),
+            [_] if meaningful.is_none() => meaningful = Some(arm),
+            _ => return None,
+        }
+    }
+    meaningful.map(|m| (m, wild))
+}
+
+fn is_wild(pat: &ast::Pat) -> bool {
+    match pat.node {
+        ast::PatKind::Wild => true,
+        _ => false,
+    }
+}
+
+/// If `pat` is a single simple binding (`Some(x)`, `x`, …) with no
+/// sub-pattern, return the bound identifier.
+fn single_bound_ident(pat: &ast::Pat) -> Option<ast::Ident> {
+    match pat.node {
+        ast::PatKind::Ident(_, ident, None) => Some(ident),
+        ast::PatKind::TupleStruct(_, ref pats, None) => match &**pats {
+            [inner] => single_bound_ident(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// If the block contains only one expression, return it.
+fn expr_block(block: &ast::Block) -> Option<&ast::Expr> {
+    let mut it = block.stmts.iter();
+
+    if let (Some(stmt), None) = (it.next(), it.next()) {
+        match stmt.node {
+            ast::StmtKind::Expr(ref expr) | ast::StmtKind::Semi(ref expr) => Some(expr),
+            _ => None,
+        }
+    } else {
+        None
+    }
+}
+
+/// Checks whether `expr` contains a reference to a single-segment path named `name`.
+fn expr_uses_ident(expr: &ast::Expr, name: ast::Name) -> bool {
+    let mut visitor = IdentUseVisitor { name, found: false };
+    visitor.visit_expr(expr);
+    visitor.found
+}
+
+/// Checks whether `block` contains a reference to a single-segment path named `name`.
+fn block_uses_ident(block: &ast::Block, name: ast::Name) -> bool {
+    let mut visitor = IdentUseVisitor { name, found: false };
+    visit::walk_block(&mut visitor, block);
+    visitor.found
+}
+
+struct IdentUseVisitor {
+    name: ast::Name,
+    found: bool,
+}
+
+impl<'ast> Visitor<'ast> for IdentUseVisitor {
+    fn visit_expr(&mut self, expr: &'ast ast::Expr) {
+        if let ast::ExprKind::Path(None, ref path) = expr.node {
+            if let [seg] = &*path.segments {
+                if seg.ident.name == self.name {
+                    self.found = true;
+                }
+            }
+        }
+        visit::walk_expr(self, expr);
+    }
+}
@@ -8,15 +8,17 @@
 // except according to those terms.
 
 
+use crate::rustc::hir::def::Def;
 use crate::rustc::hir::def_id::DefId;
 use crate::rustc::hir;
 use crate::rustc::lint::{LateContext, LateLintPass, LintArray, LintPass, in_external_macro, LintContext};
 use crate::rustc::{declare_tool_lint, lint_array};
 use if_chain::if_chain;
 use crate::rustc::ty::{self, Ty};
+use crate::syntax::ast;
 use crate::syntax::source_map::Span;
 use crate::utils::paths;
-use crate::utils::{get_trait_def_id, implements_trait, return_ty, same_tys, span_lint_and_then};
+use crate::utils::{get_trait_def_id, implements_trait, match_def_path, return_ty, same_tys, span_lint_and_then};
 use crate::utils::sugg::DiagnosticBuilderExt;
 use crate::rustc_errors::Applicability;
 
@@ -91,18 +93,105 @@ declare_clippy_lint! {
     "`fn new() -> Self` without `#[derive]`able `Default` implementation"
 }
 
+/// **What it does:** Checks for constructors that are named after the type
+/// they construct, e.g. `impl Foo { fn foo() -> Self { .. } }`.
+///
+/// **Why is this bad?** The conventional name for a constructor is `new`.
+/// Repeating the type's name is redundant and inconsistent with the rest of
+/// the ecosystem.
+///
+/// **Known problems:** None.
+///
+/// **Example:**
+/// ```rust,ignore
+/// struct Foo;
+///
+/// impl Foo {
+///     fn foo() -> Self {
+///         Foo
+///     }
+/// }
+/// ```
+///
+/// Instead, use:
+///
+/// ```rust,ignore
+/// struct Foo;
+///
+/// impl Foo {
+///     fn new() -> Self {
+///         Foo
+///     }
+/// }
+/// ```
+declare_clippy_lint! {
+    pub SELF_NAMED_CONSTRUCTOR,
+    style,
+    "method should be named `new` instead of the name of the type it constructs"
+}
+
+/// **What it does:** Checks for manual `impl Default` whose `default()` body
+/// just sets every field to that field's own default value.
+///
+/// **Why is this bad?** The same implementation can be derived, which is
+/// shorter and keeps `Default` in sync if fields are added or removed.
+///
+/// **Known problems:** Only struct literals are recognized; impls that
+/// build the value some other way (e.g. calling a constructor) are not
+/// flagged. Structs with a `#[non_exhaustive]` attribute are skipped, since
+/// the derived impl wouldn't be constructible from other crates anyway.
+///
+/// **Example:**
+/// ```rust,ignore
+/// struct Foo {
+///     bar: bool,
+///     baz: Option<u8>,
+/// }
+///
+/// impl Default for Foo {
+///     fn default() -> Self {
+///         Foo {
+///             bar: false,
+///             baz: None,
+///         }
+///     }
+/// }
+/// ```
+///
+/// Use instead:
+/// ```rust,ignore
+/// #[derive(Default)]
+/// struct Foo {
+///     bar: bool,
+///     baz: Option<u8>,
+/// }
+/// ```
+declare_clippy_lint! {
+    pub DERIVABLE_IMPLS,
+    style,
+    "manual `impl Default` that could be a `#[derive(Default)]` instead"
+}
+
 #[derive(Copy, Clone)]
 pub struct NewWithoutDefault;
 
 impl LintPass for NewWithoutDefault {
     fn get_lints(&self) -> LintArray {
-        lint_array!(NEW_WITHOUT_DEFAULT, NEW_WITHOUT_DEFAULT_DERIVE)
+        lint_array!(
+            NEW_WITHOUT_DEFAULT,
+            NEW_WITHOUT_DEFAULT_DERIVE,
+            SELF_NAMED_CONSTRUCTOR,
+            DERIVABLE_IMPLS
+        )
     }
 }
 
 impl<'a, 'tcx> LateLintPass<'a, 'tcx> for NewWithoutDefault {
     fn check_item(&mut self, cx: &LateContext<'a, 'tcx>, item: &'tcx hir::Item) {
-        if let hir::ItemKind::Impl(_, _, _, _, None, _, ref items) = item.node {
+        if let hir::ItemKind::Impl(_, _, _, _, Some(ref trait_ref), _, ref items) = item.node {
+            check_derivable_default(cx, item, trait_ref, items);
+        }
+        if let hir::ItemKind::Impl(_, _, _, _, None, ref self_ty_hir, ref items) = item.node {
             for assoc_item in items {
                 if let hir::AssociatedItemKind::Method { has_self: false } = assoc_item.kind {
                     let impl_item = cx.tcx.hir.impl_item(assoc_item.id);
@@ -125,6 +214,33 @@ impl<'a, 'tcx> LateLintPass<'a, 'tcx> for NewWithoutDefault {
                             // impl of `Default`
                             return;
                         }
+                        if cx.access_levels.is_reachable(id) {
+                            if let Some(self_ident) = self_ty_last_ident(self_ty_hir) {
+                                if name != "new" && name.as_str().to_lowercase() == self_ident.as_str().to_lowercase() {
+                                    let self_ty = cx.tcx
+                                        .type_of(cx.tcx.hir.local_def_id(cx.tcx.hir.get_parent(id)));
+                                    if same_tys(cx, self_ty, return_ty(cx, id)) {
+                                        span_lint_and_then(
+                                            cx,
+                                            SELF_NAMED_CONSTRUCTOR,
+                                            impl_item.span,
+                                            &format!(
+                                                "constructor `{}` has the same name as the type, consider renaming it to `new`",
+                                                name,
+                                            ),
+                                            |db| {
+                                                db.span_suggestion_with_applicability(
+                                                    impl_item.ident.span,
+                                                    "try",
+                                                    "new".to_string(),
+                                                    Applicability::MaybeIncorrect,
+                                                );
+                                            },
+                                        );
+                                    }
+                                }
+                            }
+                        }
                         if sig.decl.inputs.is_empty() && name == "new" && cx.access_levels.is_reachable(id) {
                             let self_ty = cx.tcx
                                 .type_of(cx.tcx.hir.local_def_id(cx.tcx.hir.get_parent(id)));
@@ -185,6 +301,118 @@ fn create_new_without_default_suggest_msg(ty: Ty<'_>) -> String {
 }}", ty)
 }
 
+/// Returns the `impl`'s self type's own identifier, stripped of any module path, e.g. `Foo` for
+/// both `Foo` and `some::path::Foo`.
+fn self_ty_last_ident(ty: &hir::Ty) -> Option<ast::Ident> {
+    match ty.node {
+        hir::TyKind::Path(hir::QPath::Resolved(_, ref path)) => path.segments.last().map(|seg| seg.ident),
+        _ => None,
+    }
+}
+
+fn check_derivable_default(cx: &LateContext<'_, '_>, item: &hir::Item, trait_ref: &hir::TraitRef, items: &[hir::ImplItemRef]) {
+    if_chain! {
+        if let Some(default_trait_id) = get_trait_def_id(cx, &paths::DEFAULT_TRAIT);
+        if trait_ref.path.def.def_id() == default_trait_id;
+        if let Some(default_item) = items.iter().find(|i| i.ident.name == "default");
+        let default_impl_item = cx.tcx.hir.impl_item(default_item.id);
+        if let hir::ImplItemKind::Method(_, body_id) = default_impl_item.node;
+        let body = cx.tcx.hir.body(body_id);
+        if let Some(tail_expr) = block_tail_expr(&body.value);
+        if let Some(adt_def) = struct_literal_adt_def(cx, tail_expr);
+        if !is_non_exhaustive(cx, adt_def.did);
+        if fields_are_all_default(cx, tail_expr);
+        if let Some(struct_span) = can_derive_default(cx.tcx.type_of(adt_def.did), cx, default_trait_id);
+        then {
+            span_lint_and_then(
+                cx,
+                DERIVABLE_IMPLS,
+                item.span,
+                "this `impl` of `Default` can be derived",
+                |db| {
+                    db.span_suggestion_with_applicability(
+                        item.span,
+                        "remove this `impl`",
+                        String::new(),
+                        Applicability::MaybeIncorrect,
+                    );
+                    db.suggest_item_with_attr(
+                        cx,
+                        struct_span,
+                        "and derive `Default` on the type instead",
+                        "#[derive(Default)]",
+                        Applicability::MaybeIncorrect,
+                    );
+                },
+            );
+        }
+    }
+}
+
+/// A method body's HIR value is the function's `{ .. }` block, not its tail expression; unwrap
+/// it so callers can look at what the body actually evaluates to. Returns `None` if the block
+/// has any leading statements -- those could have side effects, or the tail literal could depend
+/// on them, so the whole `impl` can't just be thrown away in favor of a derive.
+fn block_tail_expr(expr: &hir::Expr) -> Option<&hir::Expr> {
+    match expr.node {
+        hir::ExprKind::Block(ref block, _) if block.stmts.is_empty() => block.expr.as_ref().map(|e| &**e),
+        _ => None,
+    }
+}
+
+/// If `expr` is a struct literal, return the `AdtDef` it constructs.
+fn struct_literal_adt_def<'tcx>(cx: &LateContext<'_, 'tcx>, expr: &hir::Expr) -> Option<&'tcx ty::AdtDef> {
+    if let hir::ExprKind::Struct(_, _, None) = expr.node {
+        cx.tables.expr_ty(expr).ty_adt_def()
+    } else {
+        None
+    }
+}
+
+/// Checks that every field initializer in a struct literal is recognizably the default value for
+/// its field -- a zero/empty literal, `None`, or a call to `Default::default()`/`<_>::default()`.
+fn fields_are_all_default(cx: &LateContext<'_, '_>, expr: &hir::Expr) -> bool {
+    match expr.node {
+        hir::ExprKind::Struct(_, ref fields, None) => fields.iter().all(|f| is_default_equivalent(cx, &f.expr)),
+        _ => false,
+    }
+}
+
+fn is_default_equivalent(cx: &LateContext<'_, '_>, expr: &hir::Expr) -> bool {
+    match expr.node {
+        hir::ExprKind::Lit(ref lit) => match lit.node {
+            ast::LitKind::Bool(false) | ast::LitKind::Int(0, _) => true,
+            ast::LitKind::Str(ref s, _) => s.as_str().is_empty(),
+            ast::LitKind::Float(ref s, _) | ast::LitKind::FloatUnsuffixed(ref s) => s.as_str() == "0.0" || s.as_str() == "0",
+            _ => false,
+        },
+        // resolve all the way to the variant being constructed, rather than trusting the bare
+        // identifier `None` -- a field of an unrelated enum with its own `None` variant and a
+        // different default would otherwise be mistaken for `Option::None`
+        hir::ExprKind::Path(hir::QPath::Resolved(_, ref path)) => match path.def {
+            Def::VariantCtor(variant_id, ..) => cx
+                .tcx
+                .parent_def_id(variant_id)
+                .map_or(false, |enum_id| match_def_path(cx.tcx, enum_id, &paths::OPTION)),
+            _ => false,
+        },
+        // `Default::default()` or `<_>::default()`
+        hir::ExprKind::Call(ref callee, ref args) if args.is_empty() => match callee.node {
+            hir::ExprKind::Path(hir::QPath::Resolved(_, ref path)) => {
+                path.segments.last().map_or(false, |seg| seg.ident.name == "default")
+            },
+            hir::ExprKind::Path(hir::QPath::TypeRelative(_, ref path_seg)) => path_seg.ident.name == "default",
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Checks whether the struct behind `did` is marked `#[non_exhaustive]`.
+fn is_non_exhaustive(cx: &LateContext<'_, '_>, did: DefId) -> bool {
+    cx.tcx.get_attrs(did).iter().any(|attr| attr.check_name("non_exhaustive"))
+}
+
 fn can_derive_default<'t, 'c>(ty: Ty<'t>, cx: &LateContext<'c, 't>, default_trait_id: DefId) -> Option<Span> {
     match ty.sty {
         ty::Adt(adt_def, substs) if adt_def.is_struct() => {